@@ -1,11 +1,35 @@
 use std::io;
 
 use base64::Engine;
-use openssl::rsa::Rsa;
+use openssl::{
+    bn::BigNumContext,
+    ec::{EcGroup, EcKey, PointConversionForm},
+    nid::Nid,
+    pkey::PKey,
+    rsa::Rsa,
+};
 
 pub const DEFAULT_BITS: u32 = 4092;
 
+/// The kind of SSH key to generate.
+///
+/// ref. <https://www.ietf.org/rfc/rfc4251.txt>
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum KeyType {
+    /// RSA key of the given modulus size in bits.
+    Rsa(u32),
+    /// Ed25519 key.
+    Ed25519,
+    /// ECDSA key over the NIST P-256 curve.
+    EcdsaP256,
+    /// ECDSA key over the NIST P-384 curve.
+    EcdsaP384,
+}
+
 /// Returns a new RSA key, the private key in PEM encoding, the public key in base64 encoding.
+///
+/// Kept for backwards compatibility; new callers should prefer [`new_key_typed`]
+/// with an explicit [`KeyType`].
 pub fn new_key(bits: Option<u32>) -> io::Result<(String, String)> {
     let generated_key =
         Rsa::generate(if let Some(b) = bits { b } else { DEFAULT_BITS }).map_err(|e| {
@@ -43,6 +67,179 @@ pub fn new_key(bits: Option<u32>) -> io::Result<(String, String)> {
     Ok((pk_pem_encoded, pubkey_der_encoded))
 }
 
+/// Generates a new key of the requested [`KeyType`], returning the private key
+/// in PEM encoding and the public key as a single OpenSSH-format line
+/// (e.g. `"ssh-ed25519 AAAA..."`) that EC2 and sshd accept.
+pub fn new_key_typed(key_type: KeyType) -> io::Result<(String, String)> {
+    match key_type {
+        KeyType::Rsa(bits) => new_rsa_key(bits),
+        KeyType::Ed25519 => new_ed25519_key(),
+        KeyType::EcdsaP256 => new_ecdsa_key(Nid::X9_62_PRIME256V1),
+        KeyType::EcdsaP384 => new_ecdsa_key(Nid::SECP384R1),
+    }
+}
+
+/// Encodes an OpenSSH public key line from its algorithm name and the raw
+/// length-prefixed wire fields.
+///
+/// Each field in the blob is a 4-byte big-endian length followed by the bytes.
+/// ref. <https://www.ietf.org/rfc/rfc4251.txt>
+fn openssh_public_key(algorithm: &str, fields: &[&[u8]]) -> String {
+    let mut blob = Vec::new();
+    for field in fields {
+        blob.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        blob.extend_from_slice(field);
+    }
+    let encoded = base64::engine::general_purpose::STANDARD.encode(blob);
+    format!("{algorithm} {encoded}")
+}
+
+/// Encodes a non-negative big-endian integer as an SSH `mpint`: the minimal
+/// big-endian representation, prefixed with a single `0x00` byte when the most
+/// significant bit is set so the value stays positive.
+///
+/// ref. <https://www.ietf.org/rfc/rfc4251.txt>
+fn mpint(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    let trimmed = &bytes[start..];
+    if trimmed.is_empty() {
+        return Vec::new();
+    }
+    let mut out = Vec::with_capacity(trimmed.len() + 1);
+    if trimmed[0] & 0x80 != 0 {
+        out.push(0x00);
+    }
+    out.extend_from_slice(trimmed);
+    out
+}
+
+fn new_rsa_key(bits: u32) -> io::Result<(String, String)> {
+    let generated_key = Rsa::generate(bits).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to rsa generate {}", e),
+        )
+    })?;
+
+    let pk = generated_key.private_key_to_pem().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to derive rsa private key to pem {}", e),
+        )
+    })?;
+    let pk_pem_encoded = String::from_utf8(pk).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to convert rsa private key to string {}", e),
+        )
+    })?;
+
+    // "ssh-rsa", then the public exponent and modulus as mpints.
+    let e = mpint(&generated_key.e().to_vec());
+    let n = mpint(&generated_key.n().to_vec());
+    let pubkey = openssh_public_key("ssh-rsa", &[b"ssh-rsa", &e, &n]);
+
+    Ok((pk_pem_encoded, pubkey))
+}
+
+fn new_ed25519_key() -> io::Result<(String, String)> {
+    let generated_key = PKey::generate_ed25519().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to ed25519 generate {}", e),
+        )
+    })?;
+
+    let pk = generated_key.private_key_to_pem_pkcs8().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to derive ed25519 private key to pem {}", e),
+        )
+    })?;
+    let pk_pem_encoded = String::from_utf8(pk).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to convert ed25519 private key to string {}", e),
+        )
+    })?;
+
+    let raw = generated_key.raw_public_key().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to derive ed25519 raw public key {}", e),
+        )
+    })?;
+
+    // "ssh-ed25519" followed by the 32-byte public key.
+    let pubkey = openssh_public_key("ssh-ed25519", &[b"ssh-ed25519", &raw]);
+
+    Ok((pk_pem_encoded, pubkey))
+}
+
+fn new_ecdsa_key(nid: Nid) -> io::Result<(String, String)> {
+    let group = EcGroup::from_curve_name(nid).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to load ec group {}", e),
+        )
+    })?;
+    let ec_key = EcKey::generate(&group).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to ecdsa generate {}", e),
+        )
+    })?;
+
+    let pk = ec_key.private_key_to_pem().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to derive ecdsa private key to pem {}", e),
+        )
+    })?;
+    let pk_pem_encoded = String::from_utf8(pk).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to convert ecdsa private key to string {}", e),
+        )
+    })?;
+
+    let (algorithm, identifier) = match nid {
+        Nid::X9_62_PRIME256V1 => ("ecdsa-sha2-nistp256", "nistp256"),
+        Nid::SECP384R1 => ("ecdsa-sha2-nistp384", "nistp384"),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "unsupported ecdsa curve",
+            ))
+        }
+    };
+
+    // Uncompressed point: 0x04 || X || Y.
+    let mut ctx = BigNumContext::new().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("failed to create bignum context {}", e),
+        )
+    })?;
+    let point = ec_key
+        .public_key()
+        .to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx)
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("failed to serialize ec point {}", e),
+            )
+        })?;
+
+    // algorithm name, curve identifier, then the uncompressed point.
+    let pubkey = openssh_public_key(
+        algorithm,
+        &[algorithm.as_bytes(), identifier.as_bytes(), &point],
+    );
+
+    Ok((pk_pem_encoded, pubkey))
+}
+
 /// RUST_LOG=debug cargo test --lib -- rsa::test_key --exact --show-output
 #[test]
 fn test_key() {
@@ -50,3 +247,24 @@ fn test_key() {
     println!("{pk_encoded}");
     println!("{pubkey_encoded}");
 }
+
+/// RUST_LOG=debug cargo test --lib -- rsa::test_key_typed --exact --show-output
+#[test]
+fn test_key_typed() {
+    for key_type in [
+        KeyType::Rsa(3072),
+        KeyType::Ed25519,
+        KeyType::EcdsaP256,
+        KeyType::EcdsaP384,
+    ] {
+        let (pk_encoded, _pubkey_encoded) = new_key_typed(key_type).unwrap();
+        assert!(pk_encoded.contains("PRIVATE KEY"));
+    }
+
+    let (_, rsa) = new_key_typed(KeyType::Rsa(3072)).unwrap();
+    assert!(rsa.starts_with("ssh-rsa AAAA"));
+    let (_, ed) = new_key_typed(KeyType::Ed25519).unwrap();
+    assert!(ed.starts_with("ssh-ed25519 AAAA"));
+    let (_, ec) = new_key_typed(KeyType::EcdsaP256).unwrap();
+    assert!(ec.starts_with("ecdsa-sha2-nistp256 AAAA"));
+}
@@ -1,11 +1,78 @@
 use std::{
     fs::{self, File},
-    io::{self, Write},
-    path::Path,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 
+/// How a [`Command`] talks to the remote host.
+///
+/// `Cli` shells out to the local `ssh`/`scp` binaries (the historical
+/// behavior), whereas `Native` drives an in-process libssh2 session so the
+/// crate works on hosts without an `ssh` binary (e.g. Windows).
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
+pub enum Transport {
+    #[default]
+    Cli,
+    Native,
+}
+
+/// How the native transport authenticates to the remote host.
+///
+/// Mirrors distant's key/agent/passphrase support.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Default)]
+pub enum Auth {
+    /// Authenticate with the PEM key at `ssh_key_path`.
+    #[default]
+    KeyFile,
+    /// Authenticate through the running SSH agent.
+    Agent,
+    /// Authenticate with the key at `ssh_key_path`, unlocking it with the
+    /// given passphrase.
+    KeyFileWithPassphrase(String),
+}
+
+/// How the server's host key is verified, replacing a blind
+/// `StrictHostKeyChecking no`.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Default)]
+pub enum HostKeyCheck {
+    /// Accept any host key (equivalent to `StrictHostKeyChecking no`).
+    #[default]
+    Off,
+    /// Verify against an OpenSSH `known_hosts` file, appending the key on first
+    /// connect.
+    KnownHosts(PathBuf),
+    /// Pin an explicit `SHA256:...` fingerprint.
+    Fingerprint(String),
+}
+
+/// The default SSH port.
+pub const DEFAULT_PORT: u16 = 22;
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+/// Outcome of transferring a single regular file during a recursive SFTP sync.
+///
+/// `error` is `None` on success; a partially-failed directory sync returns one
+/// entry per file so callers can see exactly which paths failed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FileTransfer {
+    pub path: String,
+    pub bytes: u64,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct Command {
     pub ssh_key_path: String,
@@ -21,6 +88,26 @@ pub struct Command {
     pub public_ip: String,
 
     pub profile: Option<String>,
+
+    /// Selects the shell-out or in-process SSH backend.
+    #[serde(default)]
+    pub transport: Transport,
+
+    /// Remote SSH port (defaults to 22).
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    /// Optional bastion reached before connecting to this host.
+    #[serde(default)]
+    pub proxy_jump: Option<Box<Command>>,
+
+    /// How the native transport authenticates.
+    #[serde(default)]
+    pub auth: Auth,
+
+    /// How the server's host key is verified.
+    #[serde(default)]
+    pub host_key_check: HostKeyCheck,
 }
 
 /// ref. <https://doc.rust-lang.org/std/string/trait.ToString.html>
@@ -36,17 +123,17 @@ impl std::fmt::Display for Command {
 chmod 400 {ssh_key_path}
 
 # instance '{instance_id}' ({instance_state}, {availability_zone}) -- ip mode '{ip_mode}'
-ssh -o \"StrictHostKeyChecking no\" -i {ssh_key_path} {user_name}@{public_ip}
-ssh -o \"StrictHostKeyChecking no\" -i {ssh_key_path} {user_name}@{public_ip} 'tail -10 /var/log/cloud-init-output.log'
-ssh -o \"StrictHostKeyChecking no\" -i {ssh_key_path} {user_name}@{public_ip} 'tail -f /var/log/cloud-init-output.log'
+ssh {host_key_opt} -i {ssh_key_path} {ssh_opts}{user_name}@{public_ip}
+ssh {host_key_opt} -i {ssh_key_path} {ssh_opts}{user_name}@{public_ip} 'tail -10 /var/log/cloud-init-output.log'
+ssh {host_key_opt} -i {ssh_key_path} {ssh_opts}{user_name}@{public_ip} 'tail -f /var/log/cloud-init-output.log'
 
 # download a remote file to local machine
-scp -i {ssh_key_path} {user_name}@{public_ip}:REMOTE_FILE_PATH LOCAL_FILE_PATH
-scp -i {ssh_key_path} -r {user_name}@{public_ip}:REMOTE_DIRECTORY_PATH LOCAL_DIRECTORY_PATH
+scp {host_key_opt} -i {ssh_key_path} {scp_opts}{user_name}@{public_ip}:REMOTE_FILE_PATH LOCAL_FILE_PATH
+scp {host_key_opt} -i {ssh_key_path} -r {scp_opts}{user_name}@{public_ip}:REMOTE_DIRECTORY_PATH LOCAL_DIRECTORY_PATH
 
 # upload a local file to remote machine
-scp -i {ssh_key_path} LOCAL_FILE_PATH {user_name}@{public_ip}:REMOTE_FILE_PATH
-scp -i {ssh_key_path} -r LOCAL_DIRECTORY_PATH {user_name}@{public_ip}:REMOTE_DIRECTORY_PATH
+scp {host_key_opt} -i {ssh_key_path} {scp_opts}LOCAL_FILE_PATH {user_name}@{public_ip}:REMOTE_FILE_PATH
+scp {host_key_opt} -i {ssh_key_path} -r {scp_opts}LOCAL_DIRECTORY_PATH {user_name}@{public_ip}:REMOTE_DIRECTORY_PATH
 
 # AWS SSM session (requires a running SSM agent)
 # https://github.com/aws/amazon-ssm-agent/issues/131
@@ -66,6 +153,12 @@ aws ssm start-session {profile_flag}--region {region} --target {instance_id} --d
             ip_mode = self.ip_mode,
             public_ip = self.public_ip,
 
+            // ssh uses "-p", scp uses "-P"; a jump host is passed via "-J".
+            ssh_opts = self.ssh_cli_opts("-p"),
+            scp_opts = self.ssh_cli_opts("-P"),
+
+            host_key_opt = self.host_key_cli_opt(),
+
             profile_flag = if let Some(v) = &self.profile {
                 format!("--profile {v} ")
             } else {
@@ -76,10 +169,280 @@ aws ssm start-session {profile_flag}--region {region} --target {instance_id} --d
 }
 
 impl Command {
+    /// Renders the shared `-p`/`-P` port flag and `-J` jump-host flag for the
+    /// CLI output, with a trailing space when non-empty. `port_flag` is `-p`
+    /// for `ssh` and `-P` for `scp`.
+    fn ssh_cli_opts(&self, port_flag: &str) -> String {
+        let mut opts = String::new();
+        if self.port != DEFAULT_PORT {
+            opts.push_str(&format!("{port_flag} {} ", self.port));
+        }
+        if let Some(jump) = &self.proxy_jump {
+            opts.push_str(&format!("-J {}@{}", jump.user_name, jump.public_ip));
+            if jump.port != DEFAULT_PORT {
+                opts.push_str(&format!(":{}", jump.port));
+            }
+            opts.push(' ');
+        }
+        opts
+    }
+
+    /// Renders the `-o StrictHostKeyChecking`/`UserKnownHostsFile` options that
+    /// match the configured [`HostKeyCheck`]. `KnownHosts` uses `accept-new`,
+    /// so, as on the native path, an unseen host is recorded and a changed key
+    /// is rejected. `Fingerprint` renders strict checking against the default
+    /// `known_hosts`; the pinned fingerprint is compared only by the native
+    /// transport.
+    fn host_key_cli_opt(&self) -> String {
+        match &self.host_key_check {
+            HostKeyCheck::Off => "-o \"StrictHostKeyChecking no\"".to_string(),
+            HostKeyCheck::KnownHosts(path) => format!(
+                "-o \"StrictHostKeyChecking accept-new\" -o \"UserKnownHostsFile={}\"",
+                path.display()
+            ),
+            HostKeyCheck::Fingerprint(_) => "-o \"StrictHostKeyChecking yes\"".to_string(),
+        }
+    }
+
+    /// Verifies the server's host key against the configured policy after the
+    /// libssh2 handshake, failing with a clear error on mismatch.
+    fn verify_host_key(&self, sess: &ssh2::Session) -> io::Result<()> {
+        match &self.host_key_check {
+            HostKeyCheck::Off => Ok(()),
+            HostKeyCheck::Fingerprint(expected) => {
+                let hash = sess.host_key_hash(ssh2::HashType::Sha256).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::Other, "server did not present a host key")
+                })?;
+                let got = format!(
+                    "SHA256:{}",
+                    base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash)
+                );
+                let want = if expected.starts_with("SHA256:") {
+                    expected.clone()
+                } else {
+                    format!("SHA256:{expected}")
+                };
+                if got == want {
+                    Ok(())
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!(
+                            "host key fingerprint mismatch for {}: expected {want}, got {got}",
+                            self.public_ip
+                        ),
+                    ))
+                }
+            }
+            HostKeyCheck::KnownHosts(path) => {
+                let mut known = sess.known_hosts().map_err(|e| {
+                    io::Error::new(io::ErrorKind::Other, format!("failed to open known_hosts {e}"))
+                })?;
+                // A missing file is fine: the key is appended on first connect.
+                let _ = known.read_file(path, ssh2::KnownHostFileKind::OpenSSH);
+                let (key, key_type) = sess.host_key().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::Other, "server did not present a host key")
+                })?;
+                match known.check_port(&self.public_ip, self.port, key) {
+                    ssh2::CheckResult::Match => Ok(()),
+                    ssh2::CheckResult::NotFound => {
+                        let fmt = match key_type {
+                            ssh2::HostKeyType::Rsa => ssh2::KnownHostKeyFormat::SshRsa,
+                            ssh2::HostKeyType::Dss => ssh2::KnownHostKeyFormat::SshDss,
+                            ssh2::HostKeyType::Ecdsa256 => ssh2::KnownHostKeyFormat::Ecdsa256,
+                            ssh2::HostKeyType::Ecdsa384 => ssh2::KnownHostKeyFormat::Ecdsa384,
+                            ssh2::HostKeyType::Ecdsa521 => ssh2::KnownHostKeyFormat::Ecdsa521,
+                            ssh2::HostKeyType::Ed255519 => ssh2::KnownHostKeyFormat::SshEd25519,
+                            ssh2::HostKeyType::Unknown => {
+                                return Err(io::Error::new(
+                                    io::ErrorKind::Other,
+                                    format!(
+                                        "unknown host key type for {}; refusing to record it",
+                                        self.public_ip
+                                    ),
+                                ))
+                            }
+                        };
+                        known
+                            .add(&self.public_ip, key, "", fmt)
+                            .and_then(|_| known.write_file(path, ssh2::KnownHostFileKind::OpenSSH))
+                            .map_err(|e| {
+                                io::Error::new(
+                                    io::ErrorKind::Other,
+                                    format!("failed to append to known_hosts {e}"),
+                                )
+                            })?;
+                        log::info!("added new host key for {} to known_hosts", self.public_ip);
+                        Ok(())
+                    }
+                    ssh2::CheckResult::Mismatch => Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("host key mismatch for {} (possible MITM)", self.public_ip),
+                    )),
+                    ssh2::CheckResult::Failure => Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("host key check failed for {}", self.public_ip),
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Opens a TCP stream to the target host, tunneling through the bastion in
+    /// `proxy_jump` via a libssh2 `direct-tcpip` channel when one is set.
+    fn dial(&self) -> io::Result<TcpStream> {
+        match &self.proxy_jump {
+            None => TcpStream::connect(format!("{}:{}", self.public_ip, self.port)),
+            Some(jump) => {
+                // Connect and authenticate to the bastion first, then forward a
+                // loopback socket through a direct-tcpip channel to the target.
+                let bastion = jump.connect()?;
+                let channel = bastion
+                    .channel_direct_tcpip(&self.public_ip, self.port, None)
+                    .map_err(|e| {
+                        io::Error::new(
+                            io::ErrorKind::Other,
+                            format!("failed to open direct-tcpip channel {e}"),
+                        )
+                    })?;
+                // Non-blocking so the tunnel pump can service both directions.
+                bastion.set_blocking(false);
+
+                let listener = TcpListener::bind("127.0.0.1:0")?;
+                let local_addr = listener.local_addr()?;
+                // Keep the bastion session alive for as long as the tunnel runs.
+                // Exactly one local connection is forwarded per `dial` (the
+                // `TcpStream::connect` below establishes it); once that stream
+                // closes the tunnel is done and the thread exits.
+                thread::spawn(move || {
+                    let _bastion = bastion;
+                    if let Ok((sock, _)) = listener.accept() {
+                        let _ = bridge(sock, channel);
+                    }
+                });
+                TcpStream::connect(local_addr)
+            }
+        }
+    }
+
+    /// Opens an authenticated libssh2 session to the instance, honoring `port`,
+    /// `proxy_jump`, and the configured [`Auth`] method.
+    fn connect(&self) -> io::Result<ssh2::Session> {
+        let tcp = self.dial()?;
+        let mut sess = ssh2::Session::new().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to create session {e}"))
+        })?;
+        sess.set_tcp_stream(tcp);
+        sess.handshake().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed ssh handshake {e}"))
+        })?;
+
+        self.verify_host_key(&sess)?;
+
+        match &self.auth {
+            Auth::KeyFile => sess
+                .userauth_pubkey_file(&self.user_name, None, Path::new(&self.ssh_key_path), None),
+            Auth::KeyFileWithPassphrase(passphrase) => sess.userauth_pubkey_file(
+                &self.user_name,
+                None,
+                Path::new(&self.ssh_key_path),
+                Some(passphrase),
+            ),
+            Auth::Agent => sess.userauth_agent(&self.user_name),
+        }
+        .map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to authenticate {e}"))
+        })?;
+
+        if !sess.authenticated() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("authentication failed for {}@{}", self.user_name, self.public_ip),
+            ));
+        }
+        Ok(sess)
+    }
+
+    /// Executes a command over an in-process libssh2 channel, capturing stdout
+    /// and stderr and surfacing the real remote exit status.
+    fn run_native(&self, cmd: &str) -> io::Result<command_manager::Output> {
+        let sess = self.connect()?;
+        let mut channel = sess.channel_session().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to open channel {e}"))
+        })?;
+        channel.exec(cmd).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to exec '{cmd}' {e}"))
+        })?;
+
+        // Drain stdout and stderr concurrently. Reading all of stdout before
+        // touching stderr deadlocks on a blocking channel whenever the remote
+        // fills the stderr window before closing stdout, so poll both streams
+        // in non-blocking mode until EOF.
+        sess.set_blocking(false);
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut buf = [0u8; 16 * 1024];
+        loop {
+            let mut idle = true;
+            match channel.read(&mut buf) {
+                Ok(0) => {}
+                Ok(n) => {
+                    stdout.extend_from_slice(&buf[..n]);
+                    idle = false;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            match channel.stderr().read(&mut buf) {
+                Ok(0) => {}
+                Ok(n) => {
+                    stderr.extend_from_slice(&buf[..n]);
+                    idle = false;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            if channel.eof() && idle {
+                break;
+            }
+            if idle {
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+        sess.set_blocking(true);
+
+        channel.wait_close().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to close channel {e}"))
+        })?;
+        let exit_status = channel.exit_status().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to read exit status {e}"))
+        })?;
+        let stdout = String::from_utf8_lossy(&stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&stderr).into_owned();
+        // Propagate a failing remote command as an error, mirroring the CLI
+        // path where `command_manager::run` surfaces a failing process. The
+        // status is otherwise lost, since `command_manager::Output` carries no
+        // exit-code field.
+        if exit_status != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("remote command '{cmd}' exited with status {exit_status}: {stderr}"),
+            ));
+        }
+
+        Ok(command_manager::Output { stdout, stderr })
+    }
+
     /// Run a command remotely.
     pub fn run(&self, cmd: &str) -> io::Result<command_manager::Output> {
+        if self.transport == Transport::Native {
+            log::info!("running an SSH command on {} (native)", self.public_ip);
+            return self.run_native(cmd);
+        }
         log::info!("sending an SSH command to {}", self.public_ip);
-        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && ssh -o \"StrictHostKeyChecking no\" -i {ssh_key_path} {user_name}@{public_ip} '{cmd}'",
+        let host_key_opt = self.host_key_cli_opt();
+        let ssh_opts = self.ssh_cli_opts("-p");
+        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && ssh {host_key_opt} -i {ssh_key_path} {ssh_opts}{user_name}@{public_ip} '{cmd}'",
             ssh_key_path = self.ssh_key_path,
             user_name = self.user_name,
             public_ip = self.public_ip,
@@ -87,6 +450,384 @@ impl Command {
         command_manager::run(&remote_cmd_to_run)
     }
 
+    /// Runs a command over the native transport, invoking `on_line` with each
+    /// remote stdout line as it arrives instead of buffering the whole output,
+    /// and returns the final remote exit code.
+    pub fn run_streaming(
+        &self,
+        cmd: &str,
+        mut on_line: impl FnMut(&str),
+    ) -> io::Result<i32> {
+        log::info!("streaming an SSH command from {}", self.public_ip);
+        let sess = self.connect()?;
+        let mut channel = sess.channel_session().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to open channel {e}"))
+        })?;
+        channel.exec(cmd).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to exec '{cmd}' {e}"))
+        })?;
+
+        // Poll non-blocking and drain stderr alongside stdout: reading only
+        // stdout on a blocking channel deadlocks when the remote fills its
+        // stderr window before closing stdout (same hazard as `run_native`).
+        // Stdout bytes are split into lines and handed to `on_line` as they
+        // arrive; any trailing unterminated line is flushed at EOF.
+        sess.set_blocking(false);
+        let mut pending = Vec::new();
+        let mut buf = [0u8; 16 * 1024];
+        loop {
+            let mut idle = true;
+            match channel.read(&mut buf) {
+                Ok(0) => {}
+                Ok(n) => {
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+                        let rest = pending.split_off(pos + 1);
+                        let line = String::from_utf8_lossy(&pending);
+                        on_line(line.trim_end_matches(['\n', '\r']));
+                        pending = rest;
+                    }
+                    idle = false;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            // Drain stderr so a full stderr window can never block stdout.
+            match channel.stderr().read(&mut buf) {
+                Ok(0) => {}
+                Ok(_) => idle = false,
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            if channel.eof() && idle {
+                break;
+            }
+            if idle {
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+        if !pending.is_empty() {
+            let line = String::from_utf8_lossy(&pending);
+            on_line(line.trim_end_matches(['\n', '\r']));
+        }
+        sess.set_blocking(true);
+
+        channel.wait_close().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to close channel {e}"))
+        })?;
+        channel.exit_status().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to read exit status {e}"))
+        })
+    }
+
+    /// Opens an interactive PTY shell over the native transport, bridging the
+    /// local stdin/stdout to the remote session until it closes, and returns
+    /// the final remote exit code.
+    pub fn shell(&self) -> io::Result<i32> {
+        log::info!("opening an interactive shell on {}", self.public_ip);
+        let sess = self.connect()?;
+        let mut channel = sess.channel_session().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to open channel {e}"))
+        })?;
+        channel.request_pty("xterm", None, None).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to request pty {e}"))
+        })?;
+        channel.shell().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to start shell {e}"))
+        })?;
+        // Non-blocking so one loop can service both directions without a
+        // background reader thread that would block on stdin forever and leak
+        // once the channel closes. `RawTerminal` puts the local tty in raw mode
+        // (and stdin in non-blocking mode) for the duration, restoring it on
+        // drop, so keystrokes reach the remote PTY unbuffered and un-echoed.
+        sess.set_blocking(false);
+        let _raw = RawTerminal::enable()?;
+
+        let mut stdin = io::stdin();
+        let mut stdout = io::stdout();
+        let mut buf = [0u8; 16 * 1024];
+        loop {
+            let mut idle = true;
+            match stdin.read(&mut buf) {
+                Ok(0) => {}
+                Ok(n) => {
+                    write_all_blocking(&mut channel, &buf[..n])?;
+                    channel.flush()?;
+                    idle = false;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            match channel.read(&mut buf) {
+                Ok(0) => {
+                    if channel.eof() {
+                        break;
+                    }
+                }
+                Ok(n) => {
+                    stdout.write_all(&buf[..n])?;
+                    stdout.flush()?;
+                    idle = false;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            if channel.eof() {
+                break;
+            }
+            if idle {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        channel.wait_close().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to close channel {e}"))
+        })?;
+        channel.exit_status().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to read exit status {e}"))
+        })
+    }
+
+    /// Downloads a single remote file over libssh2's SCP receive channel.
+    fn download_file_native(
+        &self,
+        remote_file_path: &str,
+        local_file_path: &str,
+        overwrite: bool,
+    ) -> io::Result<command_manager::Output> {
+        log::info!("downloading '{remote_file_path}' from {} (native)", self.public_ip);
+        if Path::new(local_file_path).exists() && !overwrite {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("file '{local_file_path}' already exists"),
+            ));
+        }
+
+        let sess = self.connect()?;
+        let (mut channel, stat) = sess.scp_recv(Path::new(remote_file_path)).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to open scp recv {e}"))
+        })?;
+
+        let mut contents = Vec::with_capacity(stat.size() as usize);
+        channel.read_to_end(&mut contents)?;
+        channel.send_eof().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to send eof {e}"))
+        })?;
+        channel.wait_close().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to close channel {e}"))
+        })?;
+
+        fs::write(local_file_path, &contents)?;
+        log::info!("successfully downloaded to '{local_file_path}'");
+
+        Ok(command_manager::Output {
+            stdout: String::new(),
+            stderr: String::new(),
+        })
+    }
+
+    /// Uploads a single local file over libssh2's SCP send channel, preserving
+    /// the source file's Unix permission bits.
+    fn send_file_native(
+        &self,
+        local_file_path: &str,
+        remote_file_path: &str,
+        _overwrite: bool,
+    ) -> io::Result<command_manager::Output> {
+        log::info!("sending '{local_file_path}' to {} (native)", self.public_ip);
+        if !Path::new(local_file_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("file '{local_file_path}' does not exist"),
+            ));
+        }
+
+        let contents = fs::read(local_file_path)?;
+        let mode = file_mode(local_file_path)?;
+
+        let sess = self.connect()?;
+        let mut channel = sess
+            .scp_send(Path::new(remote_file_path), mode, contents.len() as u64, None)
+            .map_err(|e| {
+                io::Error::new(io::ErrorKind::Other, format!("failed to open scp send {e}"))
+            })?;
+        channel.write_all(&contents)?;
+        channel.send_eof().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to send eof {e}"))
+        })?;
+        channel.wait_close().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to close channel {e}"))
+        })?;
+        log::info!("successfully sent to '{remote_file_path}'");
+
+        Ok(command_manager::Output {
+            stdout: String::new(),
+            stderr: String::new(),
+        })
+    }
+
+    /// Streams a single local file to the remote host over SFTP, preserving the
+    /// source permission bits and invoking `progress` with the running
+    /// (bytes_transferred, total_bytes) as chunks are written.
+    pub fn send_file_sftp(
+        &self,
+        local_file_path: &str,
+        remote_file_path: &str,
+        mut progress: Option<&mut dyn FnMut(u64, u64)>,
+    ) -> io::Result<u64> {
+        if !Path::new(local_file_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("file '{local_file_path}' does not exist"),
+            ));
+        }
+        let sess = self.connect()?;
+        let sftp = sess.sftp().map_err(sftp_err)?;
+        let total = fs::metadata(local_file_path)?.len();
+        sftp_upload_file(
+            &sftp,
+            Path::new(local_file_path),
+            Path::new(remote_file_path),
+            file_mode(local_file_path)?,
+            0,
+            total,
+            &mut progress,
+        )
+    }
+
+    /// Streams a single remote file to the local machine over SFTP, invoking
+    /// `progress` with the running (bytes_transferred, total_bytes).
+    pub fn download_file_sftp(
+        &self,
+        remote_file_path: &str,
+        local_file_path: &str,
+        mut progress: Option<&mut dyn FnMut(u64, u64)>,
+    ) -> io::Result<u64> {
+        let sess = self.connect()?;
+        let sftp = sess.sftp().map_err(sftp_err)?;
+        let total = sftp
+            .stat(Path::new(remote_file_path))
+            .map_err(sftp_err)?
+            .size
+            .unwrap_or(0);
+        sftp_download_file(
+            &sftp,
+            Path::new(remote_file_path),
+            Path::new(local_file_path),
+            0,
+            total,
+            &mut progress,
+        )
+    }
+
+    /// Recursively uploads a local directory to the remote host over SFTP,
+    /// recreating the tree entry-by-entry and preserving Unix permission bits.
+    /// Returns one [`FileTransfer`] per regular file; failures are collected
+    /// rather than aborting the whole sync.
+    pub fn send_directory_sftp(
+        &self,
+        local_directory_path: &str,
+        remote_directory_path: &str,
+        mut progress: Option<&mut dyn FnMut(u64, u64)>,
+    ) -> io::Result<Vec<FileTransfer>> {
+        if !Path::new(local_directory_path).is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("directory '{local_directory_path}' does not exist"),
+            ));
+        }
+        let sess = self.connect()?;
+        let sftp = sess.sftp().map_err(sftp_err)?;
+
+        let local_root = Path::new(local_directory_path);
+        let remote_root = Path::new(remote_directory_path);
+        let mut files = Vec::new();
+        collect_local_files(local_root, &mut files)?;
+        let total: u64 = files.iter().map(|(_, _, size)| *size).sum();
+
+        sftp_mkdir_p(&sftp, remote_root)?;
+        let mut transferred = 0u64;
+        let mut results = Vec::new();
+        for (path, rel, size) in files {
+            let remote = remote_root.join(&rel);
+            let res = match remote.parent() {
+                Some(parent) => sftp_mkdir_p(&sftp, parent),
+                None => Ok(()),
+            }
+            .and_then(|_| file_mode(path.to_str().unwrap_or(local_directory_path)))
+            .and_then(|mode| {
+                sftp_upload_file(&sftp, &path, &remote, mode, transferred, total, &mut progress)
+            });
+            match res {
+                Ok(n) => {
+                    transferred += n;
+                    results.push(FileTransfer {
+                        path: remote.display().to_string(),
+                        bytes: n,
+                        error: None,
+                    });
+                }
+                Err(e) => {
+                    transferred += size;
+                    results.push(FileTransfer {
+                        path: remote.display().to_string(),
+                        bytes: 0,
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Recursively downloads a remote directory to the local machine over SFTP,
+    /// walking the remote tree with `readdir`/`stat` and recreating it locally.
+    /// Returns one [`FileTransfer`] per regular file.
+    pub fn download_directory_sftp(
+        &self,
+        remote_directory_path: &str,
+        local_directory_path: &str,
+        mut progress: Option<&mut dyn FnMut(u64, u64)>,
+    ) -> io::Result<Vec<FileTransfer>> {
+        let sess = self.connect()?;
+        let sftp = sess.sftp().map_err(sftp_err)?;
+
+        let remote_root = Path::new(remote_directory_path);
+        let local_root = Path::new(local_directory_path);
+        let mut files = Vec::new();
+        collect_remote_files(&sftp, remote_root, remote_root, &mut files)?;
+        let total: u64 = files.iter().map(|(_, _, size)| *size).sum();
+
+        fs::create_dir_all(local_root)?;
+        let mut transferred = 0u64;
+        let mut results = Vec::new();
+        for (remote, rel, size) in files {
+            let local = local_root.join(&rel);
+            if let Some(parent) = local.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            match sftp_download_file(&sftp, &remote, &local, transferred, total, &mut progress) {
+                Ok(n) => {
+                    transferred += n;
+                    results.push(FileTransfer {
+                        path: local.display().to_string(),
+                        bytes: n,
+                        error: None,
+                    });
+                }
+                Err(e) => {
+                    transferred += size;
+                    results.push(FileTransfer {
+                        path: local.display().to_string(),
+                        bytes: 0,
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+        Ok(results)
+    }
+
     pub fn ssm_start_session_command(&self) -> String {
         // aws ssm start-session --region [region] --target [instance ID]
         format!(
@@ -103,6 +844,9 @@ impl Command {
         local_file_path: &str,
         overwrite: bool,
     ) -> io::Result<command_manager::Output> {
+        if self.transport == Transport::Native {
+            return self.download_file_native(remote_file_path, local_file_path, overwrite);
+        }
         log::info!("sending an SCP command to {}", self.public_ip);
         if Path::new(local_file_path).exists() && !overwrite {
             return Err(io::Error::new(
@@ -116,7 +860,9 @@ impl Command {
             log::info!("successfully rm '{local_file_path}' (out {:?})", rm_out);
         };
 
-        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && scp -i {ssh_key_path} {user_name}@{public_ip}:{remote_file_path} {local_file_path}",
+        let host_key_opt = self.host_key_cli_opt();
+        let scp_opts = self.ssh_cli_opts("-P");
+        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && scp {host_key_opt} -i {ssh_key_path} {scp_opts}{user_name}@{public_ip}:{remote_file_path} {local_file_path}",
             ssh_key_path = self.ssh_key_path,
             user_name = self.user_name,
             public_ip = self.public_ip,
@@ -144,7 +890,13 @@ impl Command {
         remote_file_path: &str,
         overwrite: bool,
     ) -> io::Result<command_manager::Output> {
+        if self.transport == Transport::Native {
+            return self.send_file_native(local_file_path, remote_file_path, overwrite);
+        }
         log::info!("send_file to {}", self.public_ip);
+        let host_key_opt = self.host_key_cli_opt();
+        let ssh_opts = self.ssh_cli_opts("-p");
+        let scp_opts = self.ssh_cli_opts("-P");
         if !Path::new(local_file_path).exists() {
             return Err(io::Error::new(
                 io::ErrorKind::Other,
@@ -153,7 +905,7 @@ impl Command {
         }
 
         if overwrite {
-            let remote_rm_cmd = format!("chmod 400 {ssh_key_path} && ssh -o \"StrictHostKeyChecking no\" -i {ssh_key_path} {user_name}@{public_ip} 'sudo rm -f {remote_file_path} || true'",
+            let remote_rm_cmd = format!("chmod 400 {ssh_key_path} && ssh {host_key_opt} -i {ssh_key_path} {ssh_opts}{user_name}@{public_ip} 'sudo rm -f {remote_file_path} || true'",
                 ssh_key_path = self.ssh_key_path,
                 user_name = self.user_name,
                 public_ip = self.public_ip,
@@ -162,7 +914,7 @@ impl Command {
             log::info!("successfully rm '{remote_file_path}' (out {:?})", rm_out);
         };
 
-        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && scp -i {ssh_key_path} {local_file_path} {user_name}@{public_ip}:{remote_file_path}",
+        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && scp {host_key_opt} -i {ssh_key_path} {scp_opts}{local_file_path} {user_name}@{public_ip}:{remote_file_path}",
             ssh_key_path = self.ssh_key_path,
             user_name = self.user_name,
             public_ip = self.public_ip,
@@ -171,7 +923,7 @@ impl Command {
         );
         let out = command_manager::run(&remote_cmd_to_run)?;
 
-        let remote_ls_cmd = format!("chmod 400 {ssh_key_path} && ssh -o \"StrictHostKeyChecking no\" -i {ssh_key_path} {user_name}@{public_ip} 'ls {remote_file_path}'",
+        let remote_ls_cmd = format!("chmod 400 {ssh_key_path} && ssh {host_key_opt} -i {ssh_key_path} {ssh_opts}{user_name}@{public_ip} 'ls {remote_file_path}'",
             ssh_key_path = self.ssh_key_path,
             user_name = self.user_name,
             public_ip = self.public_ip,
@@ -192,6 +944,12 @@ impl Command {
         local_directory_path: &str,
         overwrite: bool,
     ) -> io::Result<command_manager::Output> {
+        if self.transport == Transport::Native {
+            log::info!("download_directory from {} (native sftp)", self.public_ip);
+            let results =
+                self.download_directory_sftp(remote_directory_path, local_directory_path, None)?;
+            return summarize_transfers(results);
+        }
         log::info!("download_directory from {}", self.public_ip);
         if Path::new(local_directory_path).exists() && !overwrite {
             return Err(io::Error::new(
@@ -208,7 +966,9 @@ impl Command {
             );
         };
 
-        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && scp -i {ssh_key_path} -r {user_name}@{public_ip}:{remote_directory_path} {local_directory_path}",
+        let host_key_opt = self.host_key_cli_opt();
+        let scp_opts = self.ssh_cli_opts("-P");
+        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && scp {host_key_opt} -i {ssh_key_path} -r {scp_opts}{user_name}@{public_ip}:{remote_directory_path} {local_directory_path}",
             ssh_key_path = self.ssh_key_path,
             user_name = self.user_name,
             public_ip = self.public_ip,
@@ -236,7 +996,16 @@ impl Command {
         remote_directory_path: &str,
         overwrite: bool,
     ) -> io::Result<command_manager::Output> {
+        if self.transport == Transport::Native {
+            log::info!("send_directory to {} (native sftp)", self.public_ip);
+            let results =
+                self.send_directory_sftp(local_directory_path, remote_directory_path, None)?;
+            return summarize_transfers(results);
+        }
         log::info!("send_directory to {}", self.public_ip);
+        let host_key_opt = self.host_key_cli_opt();
+        let ssh_opts = self.ssh_cli_opts("-p");
+        let scp_opts = self.ssh_cli_opts("-P");
         if !Path::new(local_directory_path).exists() {
             return Err(io::Error::new(
                 io::ErrorKind::Other,
@@ -245,7 +1014,7 @@ impl Command {
         }
 
         if overwrite {
-            let remote_rm_cmd = format!("chmod 400 {ssh_key_path} && ssh -o \"StrictHostKeyChecking no\" -i {ssh_key_path} {user_name}@{public_ip} 'sudo rm -f {remote_directory_path} || true'",
+            let remote_rm_cmd = format!("chmod 400 {ssh_key_path} && ssh {host_key_opt} -i {ssh_key_path} {ssh_opts}{user_name}@{public_ip} 'sudo rm -f {remote_directory_path} || true'",
                 ssh_key_path = self.ssh_key_path,
                 user_name = self.user_name,
                 public_ip = self.public_ip,
@@ -257,7 +1026,7 @@ impl Command {
             );
         };
 
-        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && scp -i {ssh_key_path} -r {local_directory_path} {user_name}@{public_ip}:{remote_directory_path}",
+        let remote_cmd_to_run = format!("chmod 400 {ssh_key_path} && scp {host_key_opt} -i {ssh_key_path} -r {scp_opts}{local_directory_path} {user_name}@{public_ip}:{remote_directory_path}",
             ssh_key_path = self.ssh_key_path,
             user_name = self.user_name,
             public_ip = self.public_ip,
@@ -266,7 +1035,7 @@ impl Command {
         );
         let out = command_manager::run(&remote_cmd_to_run)?;
 
-        let remote_ls_cmd = format!("chmod 400 {ssh_key_path} && ssh -o \"StrictHostKeyChecking no\" -i {ssh_key_path} {user_name}@{public_ip} 'ls {remote_directory_path}'",
+        let remote_ls_cmd = format!("chmod 400 {ssh_key_path} && ssh {host_key_opt} -i {ssh_key_path} {ssh_opts}{user_name}@{public_ip} 'ls {remote_directory_path}'",
             ssh_key_path = self.ssh_key_path,
             user_name = self.user_name,
             public_ip = self.public_ip,
@@ -281,6 +1050,359 @@ impl Command {
     }
 }
 
+/// Returns the Unix permission bits of a local file, defaulting to 0o644 on
+/// platforms that do not expose a mode.
+fn file_mode(path: &str) -> io::Result<i32> {
+    let meta = fs::metadata(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        Ok((meta.permissions().mode() & 0o777) as i32)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        Ok(0o644)
+    }
+}
+
+/// Pumps bytes both ways between a local socket and a direct-tcpip channel
+/// until either side closes, driving the proxy-jump tunnel.
+///
+/// Both endpoints are non-blocking, so a partial write returns `WouldBlock`;
+/// [`write_all_blocking`] retries instead of letting `write_all` tear the
+/// tunnel down on the first short write.
+fn bridge(mut sock: TcpStream, mut channel: ssh2::Channel) -> io::Result<()> {
+    sock.set_nonblocking(true)?;
+    let mut from_sock = [0u8; 16 * 1024];
+    let mut from_chan = [0u8; 16 * 1024];
+    loop {
+        let mut idle = true;
+        match sock.read(&mut from_sock) {
+            Ok(0) => break,
+            Ok(n) => {
+                write_all_blocking(&mut channel, &from_sock[..n])?;
+                idle = false;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+        match channel.read(&mut from_chan) {
+            Ok(0) => {
+                if channel.eof() {
+                    break;
+                }
+            }
+            Ok(n) => {
+                write_all_blocking(&mut sock, &from_chan[..n])?;
+                idle = false;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+        if idle {
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+    let _ = channel.send_eof();
+    Ok(())
+}
+
+/// Writes the whole buffer to a non-blocking writer, yielding and retrying on
+/// `WouldBlock` rather than failing as [`Write::write_all`] would.
+fn write_all_blocking<W: Write>(writer: &mut W, mut buf: &[u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        match writer.write(buf) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole buffer through tunnel",
+                ))
+            }
+            Ok(n) => buf = &buf[n..],
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(5));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Puts the local tty into raw, non-blocking mode for the lifetime of the
+/// value, restoring the saved terminal attributes on drop. This lets
+/// [`Command::shell`] forward keystrokes unbuffered and un-echoed while polling
+/// stdin and the channel from a single loop. Interactive shells require a Unix
+/// tty; other platforms return an error rather than a degraded line-buffered
+/// session.
+#[cfg(unix)]
+struct RawTerminal {
+    fd: std::os::unix::io::RawFd,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawTerminal {
+    fn enable() -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+        let fd = io::stdin().as_raw_fd();
+        unsafe {
+            if libc::isatty(fd) == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "interactive shell requires stdin to be a tty",
+                ));
+            }
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(fd, &mut termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let original = termios;
+            libc::cfmakeraw(&mut termios);
+            if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // Non-blocking stdin so the read never stalls the channel pump.
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            if flags != -1 {
+                libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+            }
+            Ok(Self { fd, original })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        unsafe {
+            let flags = libc::fcntl(self.fd, libc::F_GETFL);
+            if flags != -1 {
+                libc::fcntl(self.fd, libc::F_SETFL, flags & !libc::O_NONBLOCK);
+            }
+            libc::tcsetattr(self.fd, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct RawTerminal;
+
+#[cfg(not(unix))]
+impl RawTerminal {
+    fn enable() -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "interactive shell is only supported on Unix terminals",
+        ))
+    }
+}
+
+/// Maps an `ssh2::Error` from the SFTP subsystem into an `io::Error`.
+fn sftp_err(e: ssh2::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("sftp error {e}"))
+}
+
+/// Collapses per-file SFTP results into a single [`command_manager::Output`],
+/// erroring if any entry failed so the native path matches the CLI semantics.
+fn summarize_transfers(results: Vec<FileTransfer>) -> io::Result<command_manager::Output> {
+    let failed: Vec<&FileTransfer> = results.iter().filter(|r| r.error.is_some()).collect();
+    if !failed.is_empty() {
+        let detail = failed
+            .iter()
+            .map(|r| format!("{}: {}", r.path, r.error.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{} file(s) failed to transfer: {detail}", failed.len()),
+        ));
+    }
+    let stdout = results
+        .iter()
+        .map(|r| r.path.clone())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(command_manager::Output {
+        stdout,
+        stderr: String::new(),
+    })
+}
+
+/// Copies a chunked byte stream from `reader` to `writer`, reporting cumulative
+/// progress against `total` through the optional callback.
+fn stream_with_progress<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    base: u64,
+    total: u64,
+    progress: &mut Option<&mut dyn FnMut(u64, u64)>,
+) -> io::Result<u64> {
+    let mut buf = [0u8; 32 * 1024];
+    let mut written = 0u64;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n])?;
+        written += n as u64;
+        if let Some(cb) = progress.as_mut() {
+            // Clamp so a file that turns out larger than its pre-walk `stat`
+            // size (or a partial transfer later charged its full size) can
+            // never drive the reported total past 100%.
+            cb((base + written).min(total), total);
+        }
+    }
+    Ok(written)
+}
+
+/// Uploads one file over an open SFTP session, preserving `mode`.
+fn sftp_upload_file(
+    sftp: &ssh2::Sftp,
+    local: &Path,
+    remote: &Path,
+    mode: i32,
+    base: u64,
+    total: u64,
+    progress: &mut Option<&mut dyn FnMut(u64, u64)>,
+) -> io::Result<u64> {
+    let mut src = File::open(local)?;
+    let mut dst = sftp
+        .open_mode(
+            remote,
+            ssh2::OpenFlags::WRITE | ssh2::OpenFlags::CREATE | ssh2::OpenFlags::TRUNCATE,
+            mode,
+            ssh2::OpenType::File,
+        )
+        .map_err(sftp_err)?;
+    stream_with_progress(&mut src, &mut dst, base, total, progress)
+}
+
+/// Downloads one file over an open SFTP session, preserving the remote file's
+/// Unix permission bits so the local copy matches what the upload side writes.
+fn sftp_download_file(
+    sftp: &ssh2::Sftp,
+    remote: &Path,
+    local: &Path,
+    base: u64,
+    total: u64,
+    progress: &mut Option<&mut dyn FnMut(u64, u64)>,
+) -> io::Result<u64> {
+    let mut src = sftp.open(remote).map_err(sftp_err)?;
+    let perm = src.stat().map_err(sftp_err)?.perm;
+    let mut dst = File::create(local)?;
+    let n = stream_with_progress(&mut src, &mut dst, base, total, progress)?;
+    drop(dst);
+    if let Some(mode) = perm {
+        set_local_mode(local, mode)?;
+    }
+    Ok(n)
+}
+
+/// Applies Unix permission bits to a freshly written local file; a no-op on
+/// platforms without a mode.
+fn set_local_mode(path: &Path, mode: u32) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777))?;
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (path, mode);
+    }
+    Ok(())
+}
+
+/// Creates `dir` and all of its parents on the remote host, ignoring entries
+/// that already exist.
+fn sftp_mkdir_p(sftp: &ssh2::Sftp, dir: &Path) -> io::Result<()> {
+    let mut acc = std::path::PathBuf::new();
+    for comp in dir.components() {
+        acc.push(comp);
+        if acc.as_os_str().is_empty() {
+            continue;
+        }
+        if sftp.stat(&acc).is_ok() {
+            continue;
+        }
+        // 0o755 is the conventional directory mode.
+        if let Err(e) = sftp.mkdir(&acc, 0o755) {
+            // A concurrent creation or pre-existing directory is not fatal.
+            if sftp.stat(&acc).is_err() {
+                return Err(sftp_err(e));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Walks a local directory, collecting (absolute path, path relative to `root`,
+/// size) for every regular file beneath it.
+fn collect_local_files(
+    root: &Path,
+    out: &mut Vec<(std::path::PathBuf, std::path::PathBuf, u64)>,
+) -> io::Result<()> {
+    collect_local_files_from(root, root, out)
+}
+
+fn collect_local_files_from(
+    root: &Path,
+    dir: &Path,
+    out: &mut Vec<(std::path::PathBuf, std::path::PathBuf, u64)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let meta = entry.metadata()?;
+        if meta.is_dir() {
+            collect_local_files_from(root, &path, out)?;
+        } else if meta.is_file() {
+            let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            out.push((path, rel, meta.len()));
+        }
+    }
+    Ok(())
+}
+
+/// Walks a remote directory over SFTP, collecting (remote path, path relative
+/// to `root`, size) for every entry beneath it that is not a directory.
+fn collect_remote_files(
+    sftp: &ssh2::Sftp,
+    root: &Path,
+    dir: &Path,
+    out: &mut Vec<(std::path::PathBuf, std::path::PathBuf, u64)>,
+) -> io::Result<()> {
+    for (path, stat) in sftp.readdir(dir).map_err(sftp_err)? {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name == "." || name == ".." {
+            continue;
+        }
+        if stat.is_dir() {
+            collect_remote_files(sftp, root, &path, out)?;
+        } else {
+            let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            out.push((path.clone(), rel, stat.size.unwrap_or(0)));
+        }
+    }
+    Ok(())
+}
+
+/// Aggregate outcome of a fan-out operation across a fleet of hosts.
+///
+/// `succeeded`/`failed` count per-host operation results, where a failure is
+/// any `Err` returned by the operation. Because [`Command::run`] surfaces a
+/// nonzero remote exit status as an error on both transports, a host whose
+/// remote command fails is counted as `failed`, not `succeeded`.
+#[derive(Debug, Clone, Default)]
+pub struct FanOutSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// `(public_ip, elapsed)` of the host that took the longest, if any ran.
+    pub slowest: Option<(String, Duration)>,
+}
+
 /// A list of ssh commands.
 pub struct Commands(pub Vec<Command>);
 
@@ -303,4 +1425,106 @@ impl Commands {
 
         Ok(())
     }
+
+    /// Dispatches `op` across every host using a bounded pool of at most
+    /// `concurrency` threads, collecting a per-host result without aborting the
+    /// batch when one host fails, plus an aggregate [`FanOutSummary`].
+    fn fan_out<T, F>(
+        &self,
+        concurrency: usize,
+        op: F,
+    ) -> (Vec<(Command, io::Result<T>)>, FanOutSummary)
+    where
+        T: Send,
+        F: Fn(&Command) -> io::Result<T> + Sync,
+    {
+        let n = self.0.len();
+        let slots: Vec<Mutex<Option<(io::Result<T>, Duration)>>> =
+            (0..n).map(|_| Mutex::new(None)).collect();
+        let next = AtomicUsize::new(0);
+        let workers = concurrency.clamp(1, n.max(1));
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= n {
+                        break;
+                    }
+                    let start = Instant::now();
+                    let res = op(&self.0[i]);
+                    *slots[i].lock().unwrap() = Some((res, start.elapsed()));
+                });
+            }
+        });
+
+        let mut results = Vec::with_capacity(n);
+        let mut summary = FanOutSummary::default();
+        for (i, slot) in slots.into_iter().enumerate() {
+            let (res, elapsed) = slot.into_inner().unwrap().unwrap();
+            if res.is_ok() {
+                summary.succeeded += 1;
+            } else {
+                summary.failed += 1;
+            }
+            if summary.slowest.as_ref().map_or(true, |(_, d)| elapsed > *d) {
+                summary.slowest = Some((self.0[i].public_ip.clone(), elapsed));
+            }
+            results.push((self.0[i].clone(), res));
+        }
+        (results, summary)
+    }
+
+    /// Runs `cmd` against every host in parallel, returning a per-host result.
+    pub fn run_all(
+        &self,
+        cmd: &str,
+        concurrency: usize,
+    ) -> Vec<(Command, io::Result<command_manager::Output>)> {
+        self.fan_out(concurrency, |c| c.run(cmd)).0
+    }
+
+    /// Like [`Commands::run_all`] but also returns the aggregate summary.
+    pub fn run_all_with_summary(
+        &self,
+        cmd: &str,
+        concurrency: usize,
+    ) -> (Vec<(Command, io::Result<command_manager::Output>)>, FanOutSummary) {
+        self.fan_out(concurrency, |c| c.run(cmd))
+    }
+
+    /// Sends the same local file to every host in parallel.
+    pub fn send_all(
+        &self,
+        local_file_path: &str,
+        remote_file_path: &str,
+        overwrite: bool,
+        concurrency: usize,
+    ) -> Vec<(Command, io::Result<command_manager::Output>)> {
+        self.fan_out(concurrency, |c| {
+            c.send_file(local_file_path, remote_file_path, overwrite)
+        })
+        .0
+    }
+
+    /// Downloads `remote_file_path` from every host in parallel into
+    /// `local_directory`, prefixing each file with the host's public IP so they
+    /// do not collide.
+    pub fn download_all(
+        &self,
+        remote_file_path: &str,
+        local_directory: &str,
+        overwrite: bool,
+        concurrency: usize,
+    ) -> Vec<(Command, io::Result<command_manager::Output>)> {
+        let name = Path::new(remote_file_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("download");
+        self.fan_out(concurrency, |c| {
+            let local = format!("{local_directory}/{}-{name}", c.public_ip);
+            c.download_file(remote_file_path, &local, overwrite)
+        })
+        .0
+    }
 }